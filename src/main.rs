@@ -1,13 +1,23 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::{Parser, ValueEnum};
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Read};
+use std::sync::Mutex;
+use unicode_width::UnicodeWidthChar;
+
+/// From this many input files on, counting is farmed out to a worker pool by
+/// default even without an explicit `--jobs`.
+const PARALLEL_FILE_THRESHOLD: usize = 8;
 
 #[derive(ValueEnum, PartialEq, Clone, Default, Debug)]
 #[clap(rename_all = "lowercase")]
 enum TotalWhen {
+    /// Print the total only when more than one file was counted
+    Auto,
     #[default]
     Always,
+    /// Suppress the per-file lines and print only the total
+    Only,
     Never,
 }
 
@@ -30,6 +40,18 @@ struct Args {
     /// Show character count
     #[arg(short('m'), long, conflicts_with("bytes"))]
     chars: bool,
+    /// Show the length of the longest line, in display columns
+    #[arg(short('L'), long)]
+    max_line_length: bool,
+    /// Read input file names from FILE, separated by NUL bytes, instead of from the command
+    /// line (use '-' for FILE to read the list from stdin)
+    #[arg(long, value_name = "FILE", conflicts_with("files"))]
+    files0_from: Option<String>,
+    /// Number of worker threads to count files with (default: sequential for a few files,
+    /// scaled to available parallelism once there are many; also honored by --files0-from,
+    /// which otherwise always counts sequentially)
+    #[arg(short('j'), long, value_name = "N")]
+    jobs: Option<usize>,
     // When to print a line with total counts; WHEN can be: auto, always, only, never
     #[arg(long, default_value_t, value_enum)]
     total: TotalWhen,
@@ -41,6 +63,7 @@ struct FileInfo {
     num_words: usize,
     num_bytes: usize,
     num_chars: usize,
+    max_line_length: usize,
 }
 
 impl std::ops::AddAssign for FileInfo {
@@ -49,7 +72,25 @@ impl std::ops::AddAssign for FileInfo {
         self.num_words += rhs.num_words;
         self.num_lines += rhs.num_lines;
         self.num_chars += rhs.num_chars;
+        self.max_line_length = self.max_line_length.max(rhs.max_line_length);
+    }
+}
+
+/// Display width of a line (excluding its trailing newline), matching GNU
+/// `wc -L` semantics: a tab advances to the next multiple of 8 columns, and
+/// every other character is measured with `unicode_width` (East Asian
+/// Wide/Fullwidth characters count as 2 columns, combining marks and other
+/// zero-width characters count as 0).
+fn line_display_width(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            width += 8 - (width % 8);
+        } else {
+            width += c.width().unwrap_or(0);
+        }
     }
+    width
 }
 
 // --------------------------------------------------
@@ -74,12 +115,15 @@ fn display(lhs: &str, info: &FileInfo, args: &Args) {
     if args.bytes {
         res += &format!(" {} bytes", info.num_bytes);
     }
+    if args.max_line_length {
+        res += &format!(" {} max_line_length", info.max_line_length);
+    }
     println!("{lhs} ={res}");
 }
 
 // --------------------------------------------------
 fn run(mut args: Args) -> Result<()> {
-    if !args.words && !args.lines && !args.chars && !args.bytes {
+    if !args.words && !args.lines && !args.chars && !args.bytes && !args.max_line_length {
         args.words = true;
         args.lines = true;
         args.chars = true;
@@ -90,31 +134,69 @@ fn run(mut args: Args) -> Result<()> {
         num_words: 0,
         num_bytes: 0,
         num_chars: 0,
+        max_line_length: 0,
     };
 
-    if args.files.len() == 1 && args.files[0] == "-" {
-        let mut content = Vec::<u8>::new();
-        std::io::stdin().read_to_end(&mut content)?;
-        let content = String::from_utf8(content)?;
-        let info = count_in_str(&content);
-        display("stdin (keyboard input)", &info, &args);
-        total += info;
-    } else {
-        for filename in &args.files {
-            match open(filename) {
-                Err(err) => eprintln!("{filename}: {err}"),
-                Ok(file) => {
-                    if let Ok(info) = count_file(file) {
-                        let display_filename = if filename == "-" { "stdin" } else { filename };
-                        display(&display_filename, &info, &args);
-                        total += info;
+    let mut files_counted = 0usize;
+    let show_per_file = args.total != TotalWhen::Only;
+
+    if let Some(source) = &args.files0_from {
+        match args.jobs {
+            Some(jobs) => {
+                let jobs = jobs.max(1);
+                let batch_size = jobs * 4;
+                let mut batch = Vec::with_capacity(batch_size);
+                for_each_files0_entry(source, |filename| {
+                    batch.push(filename.to_string());
+                    if batch.len() >= batch_size {
+                        flush_batch(&mut batch, &args, jobs, show_per_file, &mut total, &mut files_counted);
                     }
+                })?;
+                if !batch.is_empty() {
+                    flush_batch(&mut batch, &args, jobs, show_per_file, &mut total, &mut files_counted);
                 }
             }
+            None => {
+                for_each_files0_entry(source, |filename| {
+                    let result = count_named(filename, &args);
+                    record_result(filename, result, &args, show_per_file, &mut total, &mut files_counted);
+                })?;
+            }
+        }
+    } else if args.files.len() == 1 && args.files[0] == "-" {
+        let info = if byte_only(&args) {
+            count_bytes_only("-")?
+        } else {
+            let mut content = Vec::<u8>::new();
+            std::io::stdin().read_to_end(&mut content)?;
+            let content = String::from_utf8(content)?;
+            count_in_str(&content)
+        };
+        accumulate("stdin (keyboard input)", info, &args, show_per_file, &mut total, &mut files_counted);
+    } else {
+        let jobs = match args.jobs {
+            Some(jobs) => jobs.max(1),
+            None if args.files.len() >= PARALLEL_FILE_THRESHOLD => std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            None => 1,
+        };
+        let results = if jobs > 1 {
+            count_files_parallel(&args.files, &args, jobs)
+        } else {
+            args.files.iter().map(|f| count_named(f, &args)).collect()
+        };
+        for (filename, result) in args.files.iter().zip(results) {
+            record_result(filename, result, &args, show_per_file, &mut total, &mut files_counted);
         }
     }
 
-    if args.total == TotalWhen::Always {
+    let show_total = match args.total {
+        TotalWhen::Always | TotalWhen::Only => true,
+        TotalWhen::Auto => files_counted > 1,
+        TotalWhen::Never => false,
+    };
+    if show_total {
         display("Total", &total, &args);
     }
     Ok(())
@@ -128,12 +210,193 @@ fn open(filename: &str) -> Result<Box<dyn BufRead>> {
     }
 }
 
+// --------------------------------------------------
+/// Count a single named input without displaying it, taking the byte-only
+/// fast path when applicable.
+fn count_named(filename: &str, args: &Args) -> Result<FileInfo> {
+    if byte_only(args) {
+        count_bytes_only(filename)
+    } else {
+        open(filename).and_then(count_file)
+    }
+}
+
+// --------------------------------------------------
+/// Display (unless `show_per_file` is false) and fold a successfully-counted
+/// input's `FileInfo` into the running total.
+fn accumulate(
+    label: &str,
+    info: FileInfo,
+    args: &Args,
+    show_per_file: bool,
+    total: &mut FileInfo,
+    files_counted: &mut usize,
+) {
+    if show_per_file {
+        display(label, &info, args);
+    }
+    *total += info;
+    *files_counted += 1;
+}
+
+/// Handle one named input's counting `result`: report-and-accumulate on
+/// success, or print an error attributed to `filename` on failure.
+fn record_result(
+    filename: &str,
+    result: Result<FileInfo>,
+    args: &Args,
+    show_per_file: bool,
+    total: &mut FileInfo,
+    files_counted: &mut usize,
+) {
+    match result {
+        Ok(info) => {
+            let display_filename = if filename == "-" { "stdin" } else { filename };
+            accumulate(display_filename, info, args, show_per_file, total, files_counted);
+        }
+        Err(err) => eprintln!("{filename}: {err}"),
+    }
+}
+
+// --------------------------------------------------
+/// Count `filenames` across a pool of `jobs` worker threads, returning
+/// results in the same order as `filenames` regardless of completion order
+/// so the caller can display and accumulate them deterministically.
+fn count_files_parallel(filenames: &[String], args: &Args, jobs: usize) -> Vec<Result<FileInfo>> {
+    let results = Mutex::new((0..filenames.len()).map(|_| None).collect::<Vec<_>>());
+    let next_index = Mutex::new(0usize);
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            let results = &results;
+            let next_index = &next_index;
+            scope.spawn(move || loop {
+                let index = {
+                    let mut next_index = next_index.lock().unwrap();
+                    if *next_index >= filenames.len() {
+                        break;
+                    }
+                    let index = *next_index;
+                    *next_index += 1;
+                    index
+                };
+                let result = count_named(&filenames[index], args);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|slot| slot.expect("every index is claimed by exactly one worker"))
+        .collect()
+}
+
+/// Count a buffered batch of `--files0-from` entries across the worker pool,
+/// report each result in order, and clear the batch for the next one. This
+/// lets `--jobs` parallelize a streamed manifest in bounded chunks instead of
+/// either ignoring `--jobs` or collecting the whole (potentially huge) file
+/// list into memory first.
+fn flush_batch(
+    batch: &mut Vec<String>,
+    args: &Args,
+    jobs: usize,
+    show_per_file: bool,
+    total: &mut FileInfo,
+    files_counted: &mut usize,
+) {
+    let results = count_files_parallel(batch, args, jobs);
+    for (filename, result) in batch.iter().zip(results) {
+        record_result(filename, result, args, show_per_file, total, files_counted);
+    }
+    batch.clear();
+}
+
+// --------------------------------------------------
+/// True when `-c`/`--bytes` is the only count being requested, the only case
+/// where we can skip reading (and UTF-8-decoding) file contents entirely.
+fn byte_only(args: &Args) -> bool {
+    args.bytes && !args.lines && !args.words && !args.chars && !args.max_line_length
+}
+
+/// Count just the bytes of `filename`, without decoding its contents. Used
+/// as a fast path for `wc -c`: a regular file's size comes straight from
+/// `fs::metadata` (`stat`/`fstat` under the hood) with no read at all;
+/// anything else (a pipe, a fifo, stdin) is drained through a fixed-size
+/// buffer and summed, still without any UTF-8 decoding.
+fn count_bytes_only(filename: &str) -> Result<FileInfo> {
+    let num_bytes = if filename == "-" {
+        drain_byte_count(io::stdin())?
+    } else {
+        let metadata = std::fs::metadata(filename)?;
+        if metadata.is_file() {
+            metadata.len() as usize
+        } else {
+            drain_byte_count(File::open(filename)?)?
+        }
+    };
+    Ok(FileInfo {
+        num_lines: 0,
+        num_words: 0,
+        num_bytes,
+        num_chars: 0,
+        max_line_length: 0,
+    })
+}
+
+fn drain_byte_count(mut reader: impl Read) -> Result<usize> {
+    let mut buf = [0u8; 64 * 1024];
+    let mut num_bytes = 0;
+    loop {
+        let bytes_read = reader.read(&mut buf)?;
+        if bytes_read == 0 {
+            break;
+        }
+        num_bytes += bytes_read;
+    }
+    Ok(num_bytes)
+}
+
+// --------------------------------------------------
+/// Stream NUL-separated file names out of `source` (a path, or `-` for
+/// stdin), invoking `f` on each one as it is read rather than collecting the
+/// whole list up front, so an arbitrarily long `--files0-from` manifest never
+/// has to fit in memory at once.
+fn for_each_files0_entry(source: &str, mut f: impl FnMut(&str)) -> Result<()> {
+    let mut reader = open(source)?;
+    let mut record = Vec::new();
+    let mut index = 0usize;
+    loop {
+        record.clear();
+        let bytes_read = reader.read_until(0, &mut record)?;
+        if bytes_read == 0 {
+            break;
+        }
+        if record.last() == Some(&0) {
+            record.pop();
+        }
+        index += 1;
+        let filename = std::str::from_utf8(&record)?;
+        if filename.is_empty() {
+            bail!("{source}: invalid zero-length file name in record {index}");
+        }
+        if filename == "-" {
+            bail!("{source}: record {index}: '-' is not allowed as a file name in --files0-from");
+        }
+        f(filename);
+    }
+    Ok(())
+}
+
 // --------------------------------------------------
 fn count_file(mut file: impl BufRead) -> Result<FileInfo> {
     let mut num_lines = 0;
     let mut num_words = 0;
     let mut num_bytes = 0;
     let mut num_chars = 0;
+    let mut max_line_length = 0;
     let mut line = String::new();
     loop {
         let line_bytes = file.read_line(&mut line)?;
@@ -141,13 +404,12 @@ fn count_file(mut file: impl BufRead) -> Result<FileInfo> {
             break;
         }
         num_bytes += line_bytes;
-        num_lines += 1;
-        num_chars += line.chars().count();
-        for l in line.split(" ") {
-            if l.trim() != "" {
-                num_words += 1;
-            }
+        if line.ends_with('\n') {
+            num_lines += 1;
         }
+        num_chars += line.chars().count();
+        max_line_length = max_line_length.max(line_display_width(line.trim_end_matches('\n')));
+        num_words += line.split_whitespace().count();
         line.clear();
     }
     Ok(FileInfo {
@@ -155,28 +417,68 @@ fn count_file(mut file: impl BufRead) -> Result<FileInfo> {
         num_words,
         num_bytes,
         num_chars,
+        max_line_length,
     })
 }
 
 fn count_in_str(text: &str) -> FileInfo {
-    let mut num_lines = 0;
-    let mut num_words = 0;
-    let mut num_bytes = 0;
-    let mut num_chars = 0;
-    for line in text.split("\n") {
-        num_bytes += line.bytes().len() + 1; // +1 for the extra byte for \n
-        num_lines += 1;
-        num_chars += line.chars().count() + 1; // +1 for the extra char for \n
-        for l in line.split(" ") {
-            if l.trim() != "" {
-                num_words += 1;
-            }
-        }
+    let num_lines = text.matches('\n').count();
+    let num_words = text.split_whitespace().count();
+    let num_bytes = text.len();
+    let num_chars = text.chars().count();
+    let mut max_line_length = 0;
+    for line in text.split('\n') {
+        max_line_length = max_line_length.max(line_display_width(line));
     }
     FileInfo {
         num_lines,
         num_words,
         num_bytes,
         num_chars,
+        max_line_length,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn count_file_counts_newline_terminated_lines() {
+        let info = count_file(Cursor::new(b"line1\nline2\n".as_slice())).unwrap();
+        assert_eq!(info.num_lines, 2);
+        assert_eq!(info.num_bytes, 12);
+        assert_eq!(info.num_chars, 12);
+    }
+
+    #[test]
+    fn count_file_does_not_count_an_unterminated_final_line() {
+        // Matches `wc -l` on a file whose last line has no trailing '\n'.
+        let info = count_file(Cursor::new(b"line1\nline2\nline3".as_slice())).unwrap();
+        assert_eq!(info.num_lines, 2);
+        assert_eq!(info.num_bytes, 17);
+        assert_eq!(info.num_chars, 17);
+    }
+
+    // Expected widths pinned against `LC_ALL=C.utf8 wc -L`.
+    #[test]
+    fn display_width_of_ascii_line() {
+        assert_eq!(line_display_width("hello"), 5);
+    }
+
+    #[test]
+    fn display_width_advances_tabs_to_next_multiple_of_eight() {
+        assert_eq!(line_display_width("\tx"), 9);
+    }
+
+    #[test]
+    fn display_width_counts_wide_cjk_chars_as_two_columns() {
+        assert_eq!(line_display_width("你好"), 4);
+    }
+
+    #[test]
+    fn display_width_ignores_combining_marks() {
+        assert_eq!(line_display_width("e\u{0301}"), 1);
     }
 }